@@ -0,0 +1,44 @@
+// desktop/apps/neuro-desktop/src/cli.rs
+//
+// Front door for configuring endpoints and exercising the integration/script
+// plumbing without a desktop: `run` drives the live websocket integration,
+// `script` runs a single high-level command script and prints its result.
+// With no subcommand, the binary keeps its original behavior (the Go
+// process + IPC handler bridge) for backwards compatibility.
+
+use clap::{Parser, Subcommand};
+
+#[derive(Parser, Debug)]
+#[command(name = "neuro-desktop", about = "Neuro Desktop control system")]
+pub struct Cli {
+    /// Neuro SDK websocket URL (overrides NEURO_SDK_WS_URL / the config file)
+    #[arg(long, global = true)]
+    pub ws_url: Option<String>,
+
+    /// Path to the IPC file used to talk to the Go integration
+    #[arg(long, global = true)]
+    pub ipc_path: Option<String>,
+
+    /// Seconds between `force_actions` nudges sent to Neuro (`run` only)
+    #[arg(long, global = true, default_value_t = 20)]
+    pub force_actions_interval: u64,
+
+    /// Route OsAgent calls through a logging stub instead of spawning the
+    /// Python driver, so the script plumbing can run on a desktop-less CI box
+    #[arg(long, global = true)]
+    pub dry_run: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Connect to the live Neuro SDK websocket
+    Run,
+    /// Execute a high-level command script and print the result; no websocket
+    Script {
+        /// Path to the script file to execute
+        file: String,
+    },
+}
@@ -1,14 +1,68 @@
 use anyhow::Result;
+use mlua::Lua;
 use pyo3::prelude::*;
 use pyo3::types::{PyTuple};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 use rust_core::paths::get_python_packages_path;
 
+use crate::events::{EventBus, NeuroEvent};
+
+/// First-line marker that routes `run_script` into the Lua engine instead of
+/// the flat line-based DSL (`TYPE`/`ENTER`/`WAIT`/`SHORTCUT`/`PRESS`).
+const LUA_SHEBANG: &str = "-- lua";
+
+/// An action queued for timed playback via [`Controller::execute_timed`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum QueuedAction {
+    MouseMove { x: i32, y: i32 },
+    MouseClick { x: i32, y: i32 },
+    TypeText { text: String },
+    KeyPress { key: String, modifiers: Option<Vec<String>> },
+}
+
+/// One structured entry of [`Controller::action_history`]: a primitive action
+/// together with the wall-clock offset (from controller startup) at which it
+/// actually ran.
+#[derive(Debug, Clone, Serialize)]
+pub struct HistoryEntry {
+    /// Milliseconds since the controller was initialized when the action ran.
+    pub realized_ms: u64,
+    pub action: String,
+    pub detail: serde_json::Value,
+    pub success: bool,
+}
+
+/// One realized entry of a timed playback, produced by
+/// [`Controller::execute_timed`] and consumed by [`Controller::replay`].
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimedAction {
+    /// Offset in ms from queue start when the action was scheduled to run.
+    pub at_ms: u64,
+    /// Offset in ms from queue start when it actually ran.
+    pub realized_ms: u64,
+    pub action: QueuedAction,
+}
+
 pub struct Controller {
     monitor: Py<PyAny>,
     mouse: Py<PyAny>,
     keyboard: Py<PyAny>,
     parser: Py<PyAny>,
+    /// Actions queued with a relative offset, awaiting `execute_timed`.
+    timed_queue: Mutex<Vec<(u64, QueuedAction)>>,
+    /// Push-based telemetry; see `events.rs`.
+    events: EventBus,
+    /// Structured, timestamped record of every primitive action executed so
+    /// far, returned by `action_history`. Recorded alongside `emit_result` so
+    /// it stays in lockstep with the events bus without proxying back through
+    /// the Python monitor.
+    history: Mutex<Vec<HistoryEntry>>,
+    /// Reference point `HistoryEntry::realized_ms` is measured from.
+    started_at: Instant,
 }
 
 impl Controller {
@@ -39,6 +93,10 @@ impl Controller {
                 mouse: tuple.get_item(1)?.into(),
                 keyboard: tuple.get_item(2)?.into(),
                 parser: tuple.get_item(3)?.into(),
+                timed_queue: Mutex::new(Vec::new()),
+                events: EventBus::new(),
+                history: Mutex::new(Vec::new()),
+                started_at: Instant::now(),
             })
         })
         .map_err(Into::into)
@@ -49,6 +107,10 @@ impl Controller {
     // =====================================================
 
     pub fn run_script(&self, script: &str) -> Result<()> {
+        if script.trim_start().starts_with(LUA_SHEBANG) {
+            return self.run_lua(script);
+        }
+
         Python::with_gil(|py| {
             self.parser
                 .bind(py)
@@ -62,6 +124,81 @@ impl Controller {
         .map_err(Into::into)
     }
 
+    /// Run `src` as Lua instead of the flat DSL, giving Neuro real control
+    /// flow (loops, conditionals, branching on the recorded action history)
+    /// over the same primitives the DSL and IPC layer use. No accessor for
+    /// screen state is exposed.
+    ///
+    /// Exposed globals: `mouse_move(x, y)`, `mouse_click(x, y)`,
+    /// `type_text(s)`, `clear_action_queue()`, `execute_instructions()`,
+    /// `wait(secs)`, and `action_history()`.
+    pub fn run_lua(&self, src: &str) -> Result<()> {
+        let lua = Lua::new();
+
+        lua.scope(|scope| {
+            let globals = lua.globals();
+
+            globals.set(
+                "mouse_move",
+                scope.create_function(|_, (x, y): (i32, i32)| {
+                    self.mouse_move(x, y)
+                        .map_err(|e| mlua::Error::RuntimeError(e.to_string()))
+                })?,
+            )?;
+
+            globals.set(
+                "mouse_click",
+                scope.create_function(|_, (x, y): (i32, i32)| {
+                    self.mouse_click(x, y, "left")
+                        .map_err(|e| mlua::Error::RuntimeError(e.to_string()))
+                })?,
+            )?;
+
+            globals.set(
+                "type_text",
+                scope.create_function(|_, text: String| {
+                    self.type_text(&text)
+                        .map_err(|e| mlua::Error::RuntimeError(e.to_string()))
+                })?,
+            )?;
+
+            globals.set(
+                "clear_action_queue",
+                scope.create_function(|_, ()| {
+                    self.clear_action_queue()
+                        .map_err(|e| mlua::Error::RuntimeError(e.to_string()))
+                })?,
+            )?;
+
+            globals.set(
+                "execute_instructions",
+                scope.create_function(|_, ()| {
+                    self.execute_instructions()
+                        .map_err(|e| mlua::Error::RuntimeError(e.to_string()))
+                })?,
+            )?;
+
+            globals.set(
+                "action_history",
+                scope.create_function(|_, ()| {
+                    self.action_history()
+                        .map_err(|e| mlua::Error::RuntimeError(e.to_string()))
+                })?,
+            )?;
+
+            globals.set(
+                "wait",
+                scope.create_function(|_, secs: f64| {
+                    std::thread::sleep(std::time::Duration::from_secs_f64(secs.max(0.0)));
+                    Ok(())
+                })?,
+            )?;
+
+            lua.load(src).exec()
+        })
+        .map_err(|e| anyhow::anyhow!("Lua script failed: {}", e))
+    }
+
     // Used to execute manual low-level calls (required when calling low-level APIs)
     pub fn execute_instructions(&self) -> Result<()> {
         Python::with_gil(|py| {
@@ -77,62 +214,174 @@ impl Controller {
     // =====================================================
 
     pub fn mouse_move(&self, x: i32, y: i32) -> Result<()> {
-        Python::with_gil(|py| {
+        let result = Python::with_gil(|py| {
             self.mouse
                 .bind(py)
                 .getattr("queue_move")?
                 .call1((x, y))?;
             Ok::<(), PyErr>(())
         })
-        .map_err(Into::into)
+        .map_err(Into::into);
+
+        self.emit_result("mouse_move", serde_json::json!({ "x": x, "y": y }), &result);
+        result
     }
 
-    pub fn mouse_click(&self, x: i32, y: i32) -> Result<()> {
-        Python::with_gil(|py| {
+    pub fn mouse_click(&self, x: i32, y: i32, button: &str) -> Result<()> {
+        let result = Python::with_gil(|py| {
             self.mouse
                 .bind(py)
                 .getattr("queue_click")?
-                .call1((x, y))?;
+                .call1((x, y, button))?;
             Ok::<(), PyErr>(())
         })
-        .map_err(Into::into)
+        .map_err(Into::into);
+
+        self.emit_result("mouse_click", serde_json::json!({ "x": x, "y": y, "button": button }), &result);
+        result
     }
 
     pub fn type_text(&self, text: &str) -> Result<()> {
-        Python::with_gil(|py| {
+        let result = Python::with_gil(|py| {
             self.keyboard
                 .bind(py)
                 .getattr("type")?
                 .call1((text,))?;
             Ok::<(), PyErr>(())
         })
-        .map_err(Into::into)
+        .map_err(Into::into);
+
+        self.emit_result("type_text", serde_json::json!({ "text": text }), &result);
+        result
+    }
+
+    /// Press `key`, optionally held with `modifiers` as a shortcut, via the
+    /// flat DSL's `PRESS`/`SHORTCUT` line (see `run_script`).
+    pub fn key_press(&self, key: &str, modifiers: Option<&[String]>) -> Result<()> {
+        let script = match modifiers {
+            Some(modifiers) if !modifiers.is_empty() => format!("SHORTCUT {} {}", modifiers.join(" "), key),
+            _ => format!("PRESS {}", key),
+        };
+
+        let result = self.run_script(&script);
+
+        self.emit_result(
+            "key_press",
+            serde_json::json!({ "key": key, "modifiers": modifiers }),
+            &result,
+        );
+        result
     }
 
     pub fn clear_action_queue(&self) -> Result<()> {
-        Python::with_gil(|py| {
+        let result = Python::with_gil(|py| {
             self.mouse.bind(py).getattr("clear")?.call0()?;
             self.keyboard.bind(py).getattr("clear")?.call0()?;
             Ok::<(), PyErr>(())
         })
-        .map_err(Into::into)
+        .map_err(Into::into);
+
+        if result.is_ok() {
+            self.events.emit(NeuroEvent::QueueCleared);
+        } else if let Err(e) = &result {
+            self.events.emit(NeuroEvent::ErrorRaised { message: format!("clear_action_queue failed: {}", e) });
+        }
+        result
+    }
+
+    /// Record the outcome of a primitive action on the event bus so
+    /// subscribers see it in real time, and append it to `history` so
+    /// `action_history` can hand back a structured, timestamped record; see
+    /// `events.rs`.
+    fn emit_result(&self, action: &str, detail: serde_json::Value, result: &Result<()>) {
+        self.history.lock().unwrap().push(HistoryEntry {
+            realized_ms: self.started_at.elapsed().as_millis() as u64,
+            action: action.to_string(),
+            detail: detail.clone(),
+            success: result.is_ok(),
+        });
+
+        match result {
+            Ok(_) => self.events.emit(NeuroEvent::ActionExecuted { action: action.to_string(), detail }),
+            Err(e) => self.events.emit(NeuroEvent::ErrorRaised { message: format!("{} failed: {}", action, e) }),
+        }
+    }
+
+    /// Subscribe to the live telemetry stream; see `events.rs`.
+    pub fn subscribe_events(&self) -> tokio::sync::broadcast::Receiver<NeuroEvent> {
+        self.events.subscribe()
+    }
+
+    // =====================================================
+    // Timestamped scheduling and replay
+    // =====================================================
+
+    /// Queue `action` to run `at_ms` milliseconds after the next
+    /// `execute_timed` call starts, instead of flushing it immediately.
+    pub fn queue_timed(&self, at_ms: u64, action: QueuedAction) {
+        self.timed_queue.lock().unwrap().push((at_ms, action));
+    }
+
+    /// Play back everything queued via `queue_timed`, honoring each action's
+    /// relative offset from queue start so event timing is deterministic.
+    /// Returns the realized, structured log of what actually ran and when.
+    pub fn execute_timed(&self) -> Result<Vec<TimedAction>> {
+        let items = {
+            let mut queue = self.timed_queue.lock().unwrap();
+            queue.sort_by_key(|(at_ms, _)| *at_ms);
+            queue.drain(..).collect::<Vec<_>>()
+        };
+
+        let start = Instant::now();
+        let mut log = Vec::with_capacity(items.len());
+
+        for (at_ms, action) in items {
+            let target = Duration::from_millis(at_ms);
+            let elapsed = start.elapsed();
+            if target > elapsed {
+                std::thread::sleep(target - elapsed);
+            }
+
+            match &action {
+                QueuedAction::MouseMove { x, y } => self.mouse_move(*x, *y)?,
+                QueuedAction::MouseClick { x, y } => self.mouse_click(*x, *y, "left")?,
+                QueuedAction::TypeText { text } => self.type_text(text)?,
+                QueuedAction::KeyPress { key, modifiers } => self.key_press(key, modifiers.as_deref())?,
+            }
+            self.execute_instructions()?;
+
+            log.push(TimedAction {
+                at_ms,
+                realized_ms: start.elapsed().as_millis() as u64,
+                action,
+            });
+        }
+
+        Ok(log)
+    }
+
+    /// Reconstruct a previously captured `execute_timed` log and re-run it at
+    /// its original cadence, for reproducible macros and regression-style
+    /// playback of recorded Neuro sessions.
+    pub fn replay(&self, history_json: &str) -> Result<Vec<TimedAction>> {
+        let history: Vec<TimedAction> = serde_json::from_str(history_json)?;
+        {
+            let mut queue = self.timed_queue.lock().unwrap();
+            queue.clear();
+            queue.extend(history.into_iter().map(|entry| (entry.at_ms, entry.action)));
+        }
+        self.execute_timed()
     }
 
     // =====================================================
     // Telemetry access
     // =====================================================
 
+    /// Structured, timestamped record of every primitive action executed so
+    /// far (see `HistoryEntry`), serialized as a JSON array.
     pub fn action_history(&self) -> Result<String> {
-        Python::with_gil(|py| {
-            let history = self
-                .monitor
-                .bind(py)
-                .getattr("get_action_history")?
-                .call0()?;
-
-            Ok::<_, PyErr>(history.str()?.to_string())
-        })
-        .map_err(Into::into)
+        let history = self.history.lock().unwrap();
+        Ok(serde_json::to_string(&*history)?)
     }
 
     /// Expose the DesktopMonitor Python object  
@@ -141,11 +390,14 @@ impl Controller {
     }
 
     pub fn shutdown(&self) -> Result<()> {
-        Python::with_gil(|py| {
+        let result = Python::with_gil(|py| {
             self.monitor.bind(py).getattr("shutdown")?.call0()?;
             Ok::<(), PyErr>(())
         })
-        .map_err(Into::into)
+        .map_err(Into::into);
+
+        self.events.emit(NeuroEvent::Shutdown);
+        result
     }
 }
 
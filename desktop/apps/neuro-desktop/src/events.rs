@@ -0,0 +1,54 @@
+// desktop/apps/neuro-desktop/src/events.rs
+//
+// Push-based telemetry: `Controller` state changes are broadcast as typed
+// `NeuroEvent`s so subscribers (the Go bridge, a dashboard) can react in
+// real time instead of polling `action_history()`.
+
+use serde::Serialize;
+use tokio::sync::broadcast;
+
+/// Capacity of the broadcast channel's internal ring buffer. A slow
+/// subscriber that falls this far behind starts missing events (it gets a
+/// `Lagged` error on its next `recv`) rather than applying backpressure to
+/// the controller.
+const CHANNEL_CAPACITY: usize = 256;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum NeuroEvent {
+    ActionExecuted { action: String, detail: serde_json::Value },
+    QueueCleared,
+    ErrorRaised { message: String },
+    Shutdown,
+}
+
+/// Thin wrapper around a `tokio::sync::broadcast` channel so `Controller`
+/// can emit events from plain synchronous code while subscribers read them
+/// over an async (or blocking-adapted) stream.
+#[derive(Clone)]
+pub struct EventBus {
+    tx: broadcast::Sender<NeuroEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (tx, _rx) = broadcast::channel(CHANNEL_CAPACITY);
+        Self { tx }
+    }
+
+    /// Broadcast `event` to every current subscriber. A no-op (not an error)
+    /// when nobody is currently subscribed.
+    pub fn emit(&self, event: NeuroEvent) {
+        let _ = self.tx.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<NeuroEvent> {
+        self.tx.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
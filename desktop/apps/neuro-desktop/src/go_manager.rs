@@ -1,8 +1,9 @@
 // desktop/apps/neuro-desktop/src/go_manager.rs
 use anyhow::{Context, Result};
-use std::process::{Child, Command};
+use std::process::{Child, Command, ExitStatus};
 use std::env;
 use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
 pub struct GoProcessManager {
     child: Option<Child>,
@@ -61,8 +62,8 @@ impl GoProcessManager {
     pub fn is_running(&mut self) -> bool {
         if let Some(child) = &mut self.child {
             match child.try_wait() {
-                Ok(Some(_)) => {
-                    println!("Neuro integration has exited");
+                Ok(Some(status)) => {
+                    println!("Neuro integration has exited ({})", status);
                     self.child = None;
                     false
                 }
@@ -77,25 +78,87 @@ impl GoProcessManager {
         }
     }
 
-    pub fn restart(&mut self, ws_url: &str, ipc_file: &str) -> Result<()> {
+    /// Stop the current process (if any) and start a new one, returning the
+    /// stopped process's exit status. Returns `None` when no process was
+    /// running, as after `is_running` has already reaped (and logged the
+    /// status of) a process that exited on its own.
+    pub fn restart(&mut self, ws_url: &str, ipc_file: &str) -> Result<Option<ExitStatus>> {
         println!("Restarting Neuro integration...");
-        self.stop();
+        let exit_status = self.stop();
         std::thread::sleep(std::time::Duration::from_millis(500));
-        self.start(ws_url, ipc_file)
+        self.start(ws_url, ipc_file)?;
+        Ok(exit_status)
     }
 
-    pub fn stop(&mut self) {
-        if let Some(mut child) = self.child.take() {
-            println!("Stopping Neuro integration...");
-            
-            match child.kill() {
-                Ok(_) => {
-                    let _ = child.wait();
-                    println!("Neuro integration stopped");
+    /// Ask the Go process to exit on its own first (SIGTERM on Unix), giving
+    /// it a grace period to stop accepting new work and flush its IPC
+    /// connection cleanly, before falling back to a hard `kill()`. Returns
+    /// the process's exit status, if one was obtained.
+    pub fn stop(&mut self) -> Option<ExitStatus> {
+        let mut child = self.child.take()?;
+        println!("Stopping Neuro integration...");
+
+        if Self::terminate_gracefully(&mut child) {
+            match Self::wait_with_timeout(&mut child, Duration::from_secs(3)) {
+                Some(status) => {
+                    println!("Neuro integration stopped gracefully ({})", status);
+                    return Some(status);
+                }
+                None => {
+                    eprintln!("Neuro integration did not exit within the grace period; killing it");
+                }
+            }
+        }
+
+        match child.kill() {
+            Ok(_) => match child.wait() {
+                Ok(status) => {
+                    println!("Neuro integration stopped ({})", status);
+                    Some(status)
                 }
                 Err(e) => {
-                    eprintln!("Failed to kill Neuro integration process: {}", e);
+                    eprintln!("Failed to reap Neuro integration process: {}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                eprintln!("Failed to kill Neuro integration process: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Send a graceful-stop signal without blocking on exit. Returns `true`
+    /// if the signal was sent and the caller should wait before killing.
+    #[cfg(unix)]
+    fn terminate_gracefully(child: &mut Child) -> bool {
+        Command::new("kill")
+            .arg("-TERM")
+            .arg(child.id().to_string())
+            .status()
+            .map(|status| status.success())
+            .unwrap_or(false)
+    }
+
+    // There is no ergonomic console-ctrl-event API available without a
+    // dedicated crate; Windows falls straight through to `kill()`.
+    #[cfg(windows)]
+    fn terminate_gracefully(_child: &mut Child) -> bool {
+        false
+    }
+
+    fn wait_with_timeout(child: &mut Child, timeout: Duration) -> Option<std::process::ExitStatus> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            match child.try_wait() {
+                Ok(Some(status)) => return Some(status),
+                Ok(None) => {
+                    if Instant::now() >= deadline {
+                        return None;
+                    }
+                    std::thread::sleep(Duration::from_millis(50));
                 }
+                Err(_) => return None,
             }
         }
     }
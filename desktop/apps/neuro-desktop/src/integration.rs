@@ -1,13 +1,48 @@
+use std::sync::Mutex;
 use std::{sync::Arc, time::Duration};
 
 use futures_util::{SinkExt, StreamExt};
 use neuro_sama::game::Api;
 use tokio::sync::mpsc;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
 use tokio_tungstenite::tungstenite::Message;
 
 use crate::controller::Controller;
+use crate::os_agent::OsAgent;
 
-struct NeuroDesktop(mpsc::UnboundedSender<Message>, Controller);
+/// Which named controller issued a command. Distinct identities let
+/// `handle_action` gate dangerous actions (e.g. arbitrary scripts) to the
+/// identity that's actually trusted with them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Identity {
+    Neuro,
+    Evil,
+}
+
+impl Identity {
+    /// Only `Neuro` is trusted to run arbitrary high-level command scripts;
+    /// `Evil` exists for adversarial testing and shouldn't get that power.
+    fn can_execute_scripts(self) -> bool {
+        matches!(self, Identity::Neuro)
+    }
+}
+
+/// Look up which identity `token` belongs to, by comparing it against the
+/// per-identity tokens configured via `NEURO_IDENTITY_TOKEN` /
+/// `EVIL_IDENTITY_TOKEN`. Returns `None` for an unrecognized token.
+fn resolve_identity(token: &str) -> Option<Identity> {
+    if !token.is_empty() {
+        if std::env::var("NEURO_IDENTITY_TOKEN").ok().as_deref() == Some(token) {
+            return Some(Identity::Neuro);
+        }
+        if std::env::var("EVIL_IDENTITY_TOKEN").ok().as_deref() == Some(token) {
+            return Some(Identity::Evil);
+        }
+    }
+    None
+}
+
+struct NeuroDesktop(mpsc::UnboundedSender<Message>, Controller, Arc<Mutex<OsAgent>>, Identity);
 
 use schemars::JsonSchema;
 use serde::Deserialize;
@@ -59,17 +94,36 @@ impl neuro_sama::game::Game for NeuroDesktop {
         Option<impl 'static + Into<std::borrow::Cow<'static, str>>>,
     > {
         match action {
-            Action::Action3(_) => Err(Some("try again")),
+            Action::Action3(_) => Err(Some("try again".to_string())),
             // This action is for manual execution of a provided command script.
             // While normally, an higher level system will be used.
 
             // That system takes an description of what neuro / evil would want
             // to do, in ENGLISH. and compiles it, into an command script.
             Action::ExecuteHLCommandScript(act) => {
-                // Execute High Level Command Script for neuro desktop
-                self.1
-                    .run_script(&act.script_contents)
-                    .map_err(|_| Some("script failed"))?;
+                if !self.3.can_execute_scripts() {
+                    return Err(Some(format!("{:?} is not authorized to run scripts", self.3)));
+                }
+
+                // Execute High Level Command Script for neuro desktop. This
+                // runs as sandboxed Lua against the `OsAgent` primitives
+                // (see `os_agent::run_script`), giving the script real
+                // control flow instead of a flat command list.
+                //
+                // `run_script` builds its own Tokio runtime to bridge its
+                // sync Lua closures to `OsAgent`'s async calls, and this
+                // method runs directly on the thread already driving
+                // `start_integration`'s runtime -- calling it inline would
+                // panic ("Cannot start a runtime from within a runtime").
+                // Running it on a plain OS thread instead sidesteps that
+                // without giving up the synchronous `handle_action` return.
+                let agent = self.2.clone();
+                let script_contents = act.script_contents.clone();
+                let result = std::thread::spawn(move || crate::os_agent::run_script(agent, &script_contents))
+                    .join()
+                    .unwrap_or_else(|_| Err("script execution thread panicked".to_string()));
+
+                result.map_err(|e| Some(format!("script failed: {e}")))?;
 
                 Ok(Some("script executed".to_string()))
             },
@@ -77,22 +131,42 @@ impl neuro_sama::game::Game for NeuroDesktop {
                 if act.b {
                     Ok(Some("ok".to_string()))
                 } else {
-                    Err(Some("err"))
+                    Err(Some("err".to_string()))
                 }
             }
         }
     }
 }
 
+/// Drive the live websocket integration. `ws_url` overrides
+/// `NEURO_SDK_WS_URL` (which in turn falls back to the local default) and
+/// `force_actions_interval` controls how often Neuro is nudged with
+/// `force_actions`. `dry_run` routes the `OsAgent` through its logging stub
+/// instead of spawning the Python driver, for desktop-less testing.
 #[tokio::main(flavor = "current_thread")]
-pub async fn start_integration(controller: Controller) {
+pub async fn start_integration(
+    controller: Controller,
+    ws_url: Option<String>,
+    force_actions_interval: u64,
+    dry_run: bool,
+) {
+    let token = std::env::var("NEURO_SDK_TOKEN").unwrap_or_default();
+    let identity = resolve_identity(&token)
+        .expect("NEURO_SDK_TOKEN does not match a configured identity; refusing to connect");
+    println!("Authenticated as identity: {:?}", identity);
+
     let (game2ws_tx, mut game2ws_rx) = mpsc::unbounded_channel();
-    let game = Arc::new(NeuroDesktop(game2ws_tx, controller));
+    let os_agent = Arc::new(Mutex::new(if dry_run {
+        OsAgent::dry_run()
+    } else {
+        OsAgent::start()
+    }));
+    let game = Arc::new(NeuroDesktop(game2ws_tx, controller, os_agent, identity));
     game.initialize().unwrap();
     let game1 = game.clone();
     tokio::spawn(async move {
         loop {
-            tokio::time::sleep(Duration::from_secs(20)).await;
+            tokio::time::sleep(Duration::from_secs(force_actions_interval)).await;
             game1
                 .force_actions::<Action>("do your thing".into())
                 .with_state("some state idk")
@@ -100,15 +174,16 @@ pub async fn start_integration(controller: Controller) {
                 .unwrap();
         }
     });
-    let mut ws =
-        tokio_tungstenite::connect_async(if let Ok(url) = std::env::var("NEURO_SDK_WS_URL") {
-            url
-        } else {
-            "ws://127.0.0.1:8000".to_owned()
-        })
-        .await
-        .unwrap()
-        .0;
+    let ws_url = ws_url
+        .or_else(|| std::env::var("NEURO_SDK_WS_URL").ok())
+        .unwrap_or_else(|| "ws://127.0.0.1:8000".to_owned());
+    let mut request = ws_url.into_client_request().unwrap();
+    request.headers_mut().insert(
+        "Authorization",
+        format!("Bearer {token}").parse().unwrap(),
+    );
+
+    let mut ws = tokio_tungstenite::connect_async(request).await.unwrap().0;
     loop {
         tokio::select! {
             msg = game2ws_rx.recv() => {
@@ -137,6 +212,43 @@ pub async fn start_integration(controller: Controller) {
                 }
             }
 
+            _ = shutdown_signal() => {
+                println!();
+                println!("Shutting down Neuro integration...");
+
+                if let Err(e) = ws.send(Message::Close(None)).await {
+                    println!("websocket close send failed: {e}");
+                } else {
+                    let _ = ws.flush().await;
+                }
+
+                // Let the Python driver exit on its own before the process
+                // is reaped, rather than leaving it orphaned.
+                if let Err(e) = game.2.lock().unwrap().quit().await {
+                    println!("OS agent quit failed: {e}");
+                }
+
+                break;
+            }
         }
     }
+}
+
+/// Resolves once the process receives Ctrl-C, or (on Unix) SIGTERM, so the
+/// main select loop can shut down cleanly instead of being killed outright.
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        let mut terminate = tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler");
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = terminate.recv() => {}
+        }
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = tokio::signal::ctrl_c().await;
+    }
 }
\ No newline at end of file
@@ -1,45 +1,63 @@
 // desktop/apps/neuro-desktop/src/ipc_handler.rs
 use anyhow::Result;
 use serde::{Deserialize, Serialize};
-use std::fs;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Read, Write};
 use std::path::PathBuf;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
 use std::thread;
-use std::time::Duration;
 
-use crate::controller::Controller;
+use crate::controller::{Controller, QueuedAction};
+use crate::plugin_manager::PluginManager;
 
 #[derive(Debug, Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum IPCCommand {
-    MouseMove { 
+    MouseMove {
         params: MouseMoveParams,
         execute_now: Option<bool>,
         clear_after: Option<bool>,
     },
-    MouseClick { 
+    MouseClick {
         params: MouseClickParams,
         execute_now: Option<bool>,
         clear_after: Option<bool>,
     },
-    KeyPress { 
+    KeyPress {
         params: KeyPressParams,
         execute_now: Option<bool>,
         clear_after: Option<bool>,
     },
-    KeyType { 
+    KeyType {
         params: KeyTypeParams,
         execute_now: Option<bool>,
         clear_after: Option<bool>,
     },
-    RunScript { 
+    RunScript {
         params: RunScriptParams,
         execute_now: Option<bool>,
         clear_after: Option<bool>,
     },
-    
+    RunLua {
+        params: RunScriptParams,
+        clear_after: Option<bool>,
+    },
+
+    Custom {
+        name: String,
+        params: serde_json::Value,
+    },
+    /// Switches this connection into streaming mode: instead of one more
+    /// request/response pair, the handler writes newline-delimited
+    /// `NeuroEvent` JSON as it happens until the client disconnects. An
+    /// empty `events` list subscribes to everything.
+    Subscribe {
+        events: Vec<String>,
+    },
+
     ExecuteQueue,
+    ExecuteTimedQueue,
     ClearActionQueue,
     ShutdownGracefully,
     ShutdownImmediately,
@@ -50,6 +68,9 @@ pub enum IPCCommand {
 pub struct MouseMoveParams {
     pub x: i32,
     pub y: i32,
+    /// Relative offset in ms from queue start; queues for `ExecuteTimedQueue`
+    /// instead of flushing immediately when set.
+    pub at_ms: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -57,17 +78,26 @@ pub struct MouseClickParams {
     pub x: Option<i32>,
     pub y: Option<i32>,
     pub button: Option<String>,
+    /// Relative offset in ms from queue start; queues for `ExecuteTimedQueue`
+    /// instead of flushing immediately when set.
+    pub at_ms: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct KeyPressParams {
     pub key: String,
     pub modifiers: Option<Vec<String>>,
+    /// Relative offset in ms from queue start; queues for `ExecuteTimedQueue`
+    /// instead of flushing immediately when set.
+    pub at_ms: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
 pub struct KeyTypeParams {
     pub text: String,
+    /// Relative offset in ms from queue start; queues for `ExecuteTimedQueue`
+    /// instead of flushing immediately when set.
+    pub at_ms: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -110,59 +140,17 @@ impl IPCResponse {
     }
 }
 
-pub struct IPCHandler {
-    ipc_file: PathBuf,
-    response_file: PathBuf,
-    running: Arc<AtomicBool>,
-}
-
-fn process_once(
-    ipc_file: &PathBuf,
-    response_file: &PathBuf,
-    controller: &Controller,
-    running: &Arc<AtomicBool>
-) -> Result<()> {
-    // Check if command file exists
-    if !ipc_file.exists() {
-        return Ok(());
-    }
-
-    // Read command
-    let data = fs::read_to_string(&ipc_file)?;
-    let command: IPCCommand = serde_json::from_str(&data)?;
-
-    // Delete command file immediately
-    fs::remove_file(&ipc_file)?;
-
-    // Execute command
-    let response = execute_command(controller, command);
-
-    // Check for shutdown signal before writing response
-    let should_shutdown = response.data.as_ref()
-        .and_then(|d| d.get("shutdown"))
-        .and_then(|s| s.as_bool())
-        .unwrap_or(false);
-
-    // Write response
-    let response_json = serde_json::to_string(&response)?;
-    fs::write(&response_file, response_json)?;
-
-    // Handle shutdown after writing response
-    if should_shutdown {
-        println!();
-        println!("Shutdown signal received, stopping IPC handler...");
-        running.store(false, Ordering::SeqCst);
-    }
-
-    Ok(())
-}
-
 fn execute_command(controller: &Controller, command: IPCCommand) -> IPCResponse {
     match command {
         IPCCommand::MouseMove { params, execute_now, clear_after } => {
+            if let Some(at_ms) = params.at_ms {
+                controller.queue_timed(at_ms, QueuedAction::MouseMove { x: params.x, y: params.y });
+                return IPCResponse::success();
+            }
+
             let execute = execute_now.unwrap_or(true);
             let clear = clear_after.unwrap_or(true);
-            
+
             match controller.mouse_move(params.x, params.y) {
                 Ok(_) => {
                     if execute {
@@ -178,9 +166,16 @@ fn execute_command(controller: &Controller, command: IPCCommand) -> IPCResponse
         }
 
         IPCCommand::MouseClick { params, execute_now, clear_after } => {
+            if let Some(at_ms) = params.at_ms {
+                let x = params.x.unwrap_or(0);
+                let y = params.y.unwrap_or(0);
+                controller.queue_timed(at_ms, QueuedAction::MouseClick { x, y });
+                return IPCResponse::success();
+            }
+
             let execute = execute_now.unwrap_or(true);
             let clear = clear_after.unwrap_or(true);
-            
+
             // If coordinates provided, move first
             if let (Some(x), Some(y)) = (params.x, params.y) {
                 if let Err(e) = controller.mouse_move(x, y) {
@@ -208,17 +203,15 @@ fn execute_command(controller: &Controller, command: IPCCommand) -> IPCResponse
         }
 
         IPCCommand::KeyPress { params, execute_now, clear_after } => {
+            if let Some(at_ms) = params.at_ms {
+                controller.queue_timed(at_ms, QueuedAction::KeyPress { key: params.key, modifiers: params.modifiers });
+                return IPCResponse::success();
+            }
+
             let execute = execute_now.unwrap_or(true);
             let clear = clear_after.unwrap_or(true);
-            
-            let script = if let Some(modifiers) = params.modifiers {
-                let mods = modifiers.join(" ");
-                format!("SHORTCUT {} {}", mods, params.key)
-            } else {
-                format!("PRESS {}", params.key)
-            };
 
-            match controller.run_script(&script) {
+            match controller.key_press(&params.key, params.modifiers.as_deref()) {
                 Ok(_) => {
                     if !execute {
                         // Script auto-executes, so this shouldn't happen
@@ -234,9 +227,14 @@ fn execute_command(controller: &Controller, command: IPCCommand) -> IPCResponse
         }
 
         IPCCommand::KeyType { params, execute_now, clear_after } => {
+            if let Some(at_ms) = params.at_ms {
+                controller.queue_timed(at_ms, QueuedAction::TypeText { text: params.text });
+                return IPCResponse::success();
+            }
+
             let execute = execute_now.unwrap_or(true);
             let clear = clear_after.unwrap_or(true);
-            
+
             match controller.type_text(&params.text) {
                 Ok(_) => {
                     if execute {
@@ -254,7 +252,7 @@ fn execute_command(controller: &Controller, command: IPCCommand) -> IPCResponse
         IPCCommand::RunScript { params, execute_now, clear_after } => {
             let execute = execute_now.unwrap_or(true);
             let clear = clear_after.unwrap_or(true);
-            
+
             match controller.run_script(&params.script) {
                 Ok(_) => {
                     // run_script auto-executes in Python
@@ -267,6 +265,20 @@ fn execute_command(controller: &Controller, command: IPCCommand) -> IPCResponse
             }
         }
 
+        IPCCommand::RunLua { params, clear_after } => {
+            let clear = clear_after.unwrap_or(true);
+
+            match controller.run_lua(&params.script) {
+                Ok(_) => {
+                    if clear {
+                        controller.clear_action_queue().ok();
+                    }
+                    IPCResponse::success()
+                }
+                Err(e) => IPCResponse::failure(format!("Lua script execution failed: {}", e)),
+            }
+        }
+
         IPCCommand::ExecuteQueue => {
             match controller.execute_instructions() {
                 Ok(_) => IPCResponse::success(),
@@ -274,53 +286,536 @@ fn execute_command(controller: &Controller, command: IPCCommand) -> IPCResponse
             }
         }
 
+        IPCCommand::ExecuteTimedQueue => {
+            match controller.execute_timed() {
+                Ok(log) => IPCResponse {
+                    success: true,
+                    data: Some(serde_json::json!({ "log": log })),
+                    error: None,
+                },
+                Err(e) => IPCResponse::failure(format!("Execute timed queue failed: {}", e)),
+            }
+        }
+
         IPCCommand::ClearActionQueue => {
             let _ = controller.clear_action_queue();
             IPCResponse::success()
         }
 
-        IPCCommand::ShutdownGracefully | IPCCommand::ShutdownImmediately => {
+        IPCCommand::ShutdownGracefully => {
+            // Drain whatever was mid-flight before tearing things down so we
+            // don't leave e.g. a key held but never released.
+            let _ = controller.clear_action_queue();
             let _ = controller.shutdown();
             IPCResponse::shutdown()
         }
+
+        IPCCommand::ShutdownImmediately => {
+            let _ = controller.shutdown();
+            IPCResponse::shutdown()
+        }
+
+        // Custom commands are routed to the PluginManager before reaching
+        // here; see handle_connection.
+        IPCCommand::Custom { name, .. } => {
+            IPCResponse::failure(format!("Command '{}' was not routed to a plugin", name))
+        }
+
+        // Subscribe switches the whole connection into streaming mode before
+        // reaching here; see handle_connection.
+        IPCCommand::Subscribe { .. } => {
+            IPCResponse::failure("Subscribe is only valid on a live connection".to_string())
+        }
+    }
+}
+
+/// A socket stream that can be duplicated so the read half and write half
+/// can be driven independently without wrapping the whole thing in a mutex.
+trait DuplexStream: Read + Write + Sized {
+    fn try_clone(&self) -> std::io::Result<Self>;
+}
+
+#[cfg(unix)]
+impl DuplexStream for std::os::unix::net::UnixStream {
+    fn try_clone(&self) -> std::io::Result<Self> {
+        std::os::unix::net::UnixStream::try_clone(self)
+    }
+}
+
+#[cfg(windows)]
+impl DuplexStream for std::net::TcpStream {
+    fn try_clone(&self) -> std::io::Result<Self> {
+        std::net::TcpStream::try_clone(self)
+    }
+}
+
+/// Switch a connection into streaming mode: write newline-delimited
+/// `NeuroEvent` JSON as the controller emits it, filtered to `filter`'s
+/// event-type names (unfiltered when empty), until the client disconnects
+/// or `running` clears.
+fn stream_events<S: DuplexStream>(
+    writer: &mut S,
+    controller: &Arc<Mutex<Controller>>,
+    filter: &[String],
+    running: &Arc<AtomicBool>,
+) -> Result<()> {
+    use tokio::sync::broadcast::error::RecvError;
+
+    let mut receiver = {
+        let controller = controller.lock().unwrap();
+        controller.subscribe_events()
+    };
+
+    // The event bus is async (`tokio::sync::broadcast`) but this connection
+    // is served on a plain std thread, so drive one `recv` at a time on a
+    // throwaway current-thread runtime instead of making the whole IPC
+    // transport async.
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build()?;
+
+    while running.load(Ordering::SeqCst) {
+        let event = match runtime.block_on(receiver.recv()) {
+            Ok(event) => event,
+            Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => break,
+        };
+
+        let value = serde_json::to_value(&event)?;
+        if !filter.is_empty() {
+            let kind = value.get("type").and_then(|t| t.as_str()).unwrap_or("");
+            if !filter.iter().any(|f| f == kind) {
+                continue;
+            }
+        }
+
+        let mut line = serde_json::to_string(&value)?;
+        line.push('\n');
+        if writer.write_all(line.as_bytes()).is_err() {
+            break;
+        }
+        writer.flush().ok();
+    }
+
+    Ok(())
+}
+
+/// Where a connection's current command sits relative to actually running
+/// it. A `Pending` command has only been read off the wire -- cancelling it
+/// is safe, since it never touched the controller -- while `Executing` means
+/// it already has, so it can only be waited out, never cancelled or
+/// replayed without risking it running twice. See `DrainHandle::drain`.
+enum InFlightState {
+    Pending(String),
+    Executing,
+}
+
+/// One entry per live connection, keyed by a per-connection id assigned in
+/// `accept_loop`. A `HashMap` (rather than a single shared slot) is needed
+/// because chunk0-1 explicitly supports multiple concurrent clients.
+type InFlightRegistry = Arc<Mutex<HashMap<u64, InFlightState>>>;
+
+/// Serve newline-delimited `IPCCommand`/`IPCResponse` pairs over a single
+/// connection until the client disconnects or `running` clears.
+fn handle_connection<S: DuplexStream>(
+    stream: S,
+    controller: &Arc<Mutex<Controller>>,
+    plugin_manager: &Arc<PluginManager>,
+    in_flight: &InFlightRegistry,
+    connection_id: u64,
+    running: &Arc<AtomicBool>,
+) -> Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut writer = stream;
+    let mut line = String::new();
+
+    loop {
+        if !running.load(Ordering::SeqCst) {
+            break;
+        }
+
+        line.clear();
+        // read_line buffers internally until it sees a '\n', so a command
+        // split across multiple TCP/socket reads is handled transparently.
+        let bytes_read = reader.read_line(&mut line)?;
+        if bytes_read == 0 {
+            // Client closed the connection.
+            break;
+        }
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+
+        // Record this command as pending under our own connection id so a
+        // concurrent drain can tell it apart from every other connection's
+        // command, and can still tell "just read" from "already running".
+        in_flight
+            .lock()
+            .unwrap()
+            .insert(connection_id, InFlightState::Pending(trimmed.to_string()));
+
+        // Commit to running it: flip our slot from `Pending` to `Executing`.
+        // If a concurrent `drain` already claimed (removed) it instead, the
+        // command is being journaled for replay once `resume` runs, so we
+        // must not also run it here -- that would actuate it twice.
+        let committed = {
+            let mut registry = in_flight.lock().unwrap();
+            match registry.get(&connection_id) {
+                Some(InFlightState::Pending(_)) => {
+                    registry.insert(connection_id, InFlightState::Executing);
+                    true
+                }
+                _ => false,
+            }
+        };
+        if !committed {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<IPCCommand>(trimmed) {
+            Ok(IPCCommand::Custom { name, params }) => {
+                plugin_manager.dispatch(controller, &name, params)
+            }
+            Ok(IPCCommand::Subscribe { events }) => {
+                in_flight.lock().unwrap().remove(&connection_id);
+                return stream_events(&mut writer, controller, &events, running);
+            }
+            Ok(command) => {
+                // Hold the lock only for the duration of one command so the
+                // Python GIL access stays serialized across connections.
+                let controller = controller.lock().unwrap();
+                execute_command(&controller, command)
+            }
+            Err(e) => IPCResponse::failure(format!("Invalid command: {}", e)),
+        };
+
+        let should_shutdown = response
+            .data
+            .as_ref()
+            .and_then(|d| d.get("shutdown"))
+            .and_then(|s| s.as_bool())
+            .unwrap_or(false);
+
+        let mut response_json = serde_json::to_string(&response)?;
+        response_json.push('\n');
+        writer.write_all(response_json.as_bytes())?;
+        writer.flush()?;
+        in_flight.lock().unwrap().remove(&connection_id);
+
+        if should_shutdown {
+            println!();
+            println!("Shutdown signal received, stopping IPC handler...");
+            running.store(false, Ordering::SeqCst);
+            break;
+        }
     }
+
+    in_flight.lock().unwrap().remove(&connection_id);
+    Ok(())
+}
+
+pub struct IPCHandler {
+    socket_path: PathBuf,
+    journal_path: PathBuf,
+    plugin_paths: Vec<String>,
+    running: Arc<AtomicBool>,
+}
+
+/// Lets `main`'s crash-monitor loop drain the IPC handler before tearing
+/// down the Go process, so an in-flight command isn't silently lost: new
+/// connections are paused, anything only just read off the wire is journaled
+/// to disk (safe, since it was never actually run) so it can be replayed
+/// once the handler resumes accepting, and anything already committed to
+/// running against the controller is waited out rather than journaled --
+/// replaying it too would run it a second time.
+#[derive(Clone)]
+pub struct DrainHandle {
+    draining: Arc<AtomicBool>,
+    in_flight: InFlightRegistry,
+    journal_path: PathBuf,
+    controller: Arc<Mutex<Controller>>,
+}
+
+impl DrainHandle {
+    /// Pause new connections. Every `Pending` command (read but not yet
+    /// executing) is claimed and journaled immediately, since it's still
+    /// safe to cancel. Past `timeout`, a warning is logged if any connection
+    /// is still `Executing`, but draining keeps waiting for it regardless --
+    /// journaling a command that's already running would let it execute
+    /// again when `resume` replays the journal.
+    pub fn drain(&self, timeout: std::time::Duration) {
+        self.draining.store(true, Ordering::SeqCst);
+
+        let deadline = std::time::Instant::now() + timeout;
+        let mut warned = false;
+        loop {
+            let claimed: Vec<String> = {
+                let mut registry = self.in_flight.lock().unwrap();
+                let pending_ids: Vec<u64> = registry
+                    .iter()
+                    .filter(|(_, state)| matches!(state, InFlightState::Pending(_)))
+                    .map(|(id, _)| *id)
+                    .collect();
+                pending_ids
+                    .into_iter()
+                    .filter_map(|id| match registry.remove(&id) {
+                        Some(InFlightState::Pending(raw)) => Some(raw),
+                        _ => None,
+                    })
+                    .collect()
+            };
+            for raw in claimed {
+                self.append_journal(&raw);
+            }
+
+            let still_executing = self
+                .in_flight
+                .lock()
+                .unwrap()
+                .values()
+                .any(|state| matches!(state, InFlightState::Executing));
+            if !still_executing {
+                return;
+            }
+
+            if !warned && std::time::Instant::now() >= deadline {
+                eprintln!(
+                    "Still waiting on an in-flight command past the {:?} grace period; \
+                     refusing to journal it while it's still running",
+                    timeout
+                );
+                warned = true;
+            }
+
+            thread::sleep(std::time::Duration::from_millis(20));
+        }
+    }
+
+    /// Append `raw` as one more line of the on-disk journal, so draining
+    /// multiple connections' pending commands doesn't clobber each other.
+    fn append_journal(&self, raw: &str) {
+        use std::io::Write as _;
+
+        let result = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.journal_path)
+            .and_then(|mut f| writeln!(f, "{}", raw));
+
+        match result {
+            Ok(()) => println!("Journaled an in-flight command to {}", self.journal_path.display()),
+            Err(e) => eprintln!("Failed to journal in-flight command: {}", e),
+        }
+    }
+
+    /// Resume accepting new connections (e.g. once the Go process has come
+    /// back up after a restart). Replays whatever `drain` journaled first,
+    /// same as `IPCHandler::start`'s boot-time replay, so a command that was
+    /// mid-flight when the Go process died isn't stranded on disk until the
+    /// whole binary restarts.
+    pub fn resume(&self) {
+        replay_journal(&self.controller, &self.journal_path);
+        self.draining.store(false, Ordering::SeqCst);
+    }
+}
+
+/// Replay every command journaled in `journal_path` (one per line) against
+/// `controller`, then delete the journal. Shared by `IPCHandler::start`'s
+/// boot-time replay and `DrainHandle::resume`.
+fn replay_journal(controller: &Arc<Mutex<Controller>>, journal_path: &PathBuf) {
+    let Ok(raw) = std::fs::read_to_string(journal_path) else {
+        return;
+    };
+    if raw.trim().is_empty() {
+        let _ = std::fs::remove_file(journal_path);
+        return;
+    }
+
+    println!("Replaying journaled commands from {}", journal_path.display());
+    for line in raw.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match serde_json::from_str::<IPCCommand>(line) {
+            Ok(command) => {
+                let controller = controller.lock().unwrap();
+                let _ = execute_command(&controller, command);
+            }
+            Err(e) => eprintln!("Failed to parse journaled command: {}", e),
+        }
+    }
+    let _ = std::fs::remove_file(journal_path);
 }
 
 impl IPCHandler {
     pub fn new(ipc_path: &str) -> Self {
-        let ipc_file = PathBuf::from(ipc_path);
-        let response_file = PathBuf::from(format!("{}.response", ipc_path));
+        Self::with_plugins(ipc_path, Vec::new())
+    }
 
+    pub fn with_plugins(ipc_path: &str, plugin_paths: Vec<String>) -> Self {
         Self {
-            ipc_file,
-            response_file,
+            socket_path: PathBuf::from(ipc_path),
+            journal_path: PathBuf::from(format!("{}.journal", ipc_path)),
+            plugin_paths,
             running: Arc::new(AtomicBool::new(false)),
         }
     }
 
-    pub fn start(self, controller: Controller) -> Arc<AtomicBool> {
-        let running = Arc::clone(&self.running);
-        let running_clone = Arc::clone(&self.running);
-        running.store(true, Ordering::SeqCst);
-        
-        let ipc_file = self.ipc_file.clone();
-        let response_file = self.response_file.clone();
-        
-        thread::spawn(move || {
-            loop {
-                if !running_clone.load(Ordering::SeqCst) {
-                    break;
+    #[cfg(unix)]
+    fn accept_loop(
+        socket_path: PathBuf,
+        controller: Arc<Mutex<Controller>>,
+        plugin_manager: Arc<PluginManager>,
+        in_flight: InFlightRegistry,
+        draining: Arc<AtomicBool>,
+        running: Arc<AtomicBool>,
+    ) {
+        use std::os::unix::net::UnixListener;
+
+        // Remove a stale socket left behind by a previous, uncleanly-stopped run.
+        let _ = std::fs::remove_file(&socket_path);
+
+        let listener = match UnixListener::bind(&socket_path) {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!("IPC handler failed to bind {}: {}", socket_path.display(), e);
+                return;
+            }
+        };
+        listener.set_nonblocking(true).ok();
+
+        let mut next_connection_id: u64 = 0;
+
+        while running.load(Ordering::SeqCst) {
+            if draining.load(Ordering::SeqCst) {
+                thread::sleep(std::time::Duration::from_millis(50));
+                continue;
+            }
+
+            match listener.accept() {
+                Ok((stream, _addr)) => {
+                    stream.set_nonblocking(false).ok();
+                    let connection_id = next_connection_id;
+                    next_connection_id += 1;
+                    let controller = Arc::clone(&controller);
+                    let plugin_manager = Arc::clone(&plugin_manager);
+                    let in_flight = Arc::clone(&in_flight);
+                    let running = Arc::clone(&running);
+                    thread::spawn(move || {
+                        if let Err(e) = handle_connection(stream, &controller, &plugin_manager, &in_flight, connection_id, &running) {
+                            eprintln!("IPC connection error: {}", e);
+                        }
+                    });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    thread::sleep(std::time::Duration::from_millis(10));
+                }
+                Err(e) => {
+                    eprintln!("IPC accept error: {}", e);
+                }
+            }
+        }
+
+        let _ = std::fs::remove_file(&socket_path);
+        println!("Stopped IPC handler");
+    }
+
+    /// Windows has no ergonomic Unix-domain-socket story here, so fall back to
+    /// a loopback TCP port. The configured path is kept only for logging.
+    #[cfg(windows)]
+    fn accept_loop(
+        socket_path: PathBuf,
+        controller: Arc<Mutex<Controller>>,
+        plugin_manager: Arc<PluginManager>,
+        in_flight: InFlightRegistry,
+        draining: Arc<AtomicBool>,
+        running: Arc<AtomicBool>,
+    ) {
+        use std::net::TcpListener;
+
+        const FALLBACK_ADDR: &str = "127.0.0.1:48157";
+
+        let listener = match TcpListener::bind(FALLBACK_ADDR) {
+            Ok(listener) => listener,
+            Err(e) => {
+                eprintln!(
+                    "IPC handler failed to bind {} (configured path {}): {}",
+                    FALLBACK_ADDR,
+                    socket_path.display(),
+                    e
+                );
+                return;
+            }
+        };
+        listener.set_nonblocking(true).ok();
+
+        let mut next_connection_id: u64 = 0;
+
+        while running.load(Ordering::SeqCst) {
+            if draining.load(Ordering::SeqCst) {
+                thread::sleep(std::time::Duration::from_millis(50));
+                continue;
+            }
+
+            match listener.accept() {
+                Ok((stream, _addr)) => {
+                    stream.set_nonblocking(false).ok();
+                    let connection_id = next_connection_id;
+                    next_connection_id += 1;
+                    let controller = Arc::clone(&controller);
+                    let plugin_manager = Arc::clone(&plugin_manager);
+                    let in_flight = Arc::clone(&in_flight);
+                    let running = Arc::clone(&running);
+                    thread::spawn(move || {
+                        if let Err(e) = handle_connection(stream, &controller, &plugin_manager, &in_flight, connection_id, &running) {
+                            eprintln!("IPC connection error: {}", e);
+                        }
+                    });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    thread::sleep(std::time::Duration::from_millis(10));
                 }
-                
-                let result = process_once(&ipc_file, &response_file, &controller, &running_clone);
-                if let Err(e) = result {
-                    eprintln!("IPC processing error: {}", e);
+                Err(e) => {
+                    eprintln!("IPC accept error: {}", e);
                 }
-                thread::sleep(Duration::from_millis(50));
             }
-            println!("Stopped IPC handler");
+        }
+
+        println!("Stopped IPC handler");
+    }
+
+    /// Starts serving IPC connections and returns the running flag plus a
+    /// `DrainHandle` for graceful restarts. If a command was journaled by a
+    /// previous drain, it's replayed against `controller` first.
+    pub fn start(self, controller: Controller) -> (Arc<AtomicBool>, DrainHandle) {
+        let running = Arc::clone(&self.running);
+        running.store(true, Ordering::SeqCst);
+
+        let draining = Arc::new(AtomicBool::new(false));
+        let in_flight: InFlightRegistry = Arc::new(Mutex::new(HashMap::new()));
+        let controller = Arc::new(Mutex::new(controller));
+
+        replay_journal(&controller, &self.journal_path);
+
+        let plugin_manager = Arc::new(PluginManager::load(&self.plugin_paths));
+        let socket_path = self.socket_path.clone();
+        let accept_running = Arc::clone(&running);
+        let accept_draining = Arc::clone(&draining);
+        let accept_in_flight = Arc::clone(&in_flight);
+        let drain_controller = Arc::clone(&controller);
+
+        thread::spawn(move || {
+            Self::accept_loop(socket_path, controller, plugin_manager, accept_in_flight, accept_draining, accept_running);
         });
 
-        running
+        let drain_handle = DrainHandle {
+            draining,
+            in_flight,
+            journal_path: self.journal_path,
+            controller: drain_controller,
+        };
+
+        (running, drain_handle)
     }
-}
\ No newline at end of file
+}
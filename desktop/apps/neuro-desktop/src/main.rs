@@ -2,10 +2,17 @@
 // desktop/apps/neuro-desktop/src/main.rs
 // ============================================================
 
+mod cli;
 mod controller;
+mod events;
+mod integration;
 mod ipc_handler;
 mod go_manager;
+mod os_agent;
+mod plugin_manager;
 
+use clap::Parser;
+use cli::{Cli, Command};
 use controller::Controller;
 use ipc_handler::IPCHandler;
 use go_manager::GoProcessManager;
@@ -18,6 +25,8 @@ use serde::{Deserialize};
 #[derive(Debug, Deserialize)]
 struct IntegrationConfig {
     connection: ConnectionConfig,
+    #[serde(default)]
+    plugins: Vec<String>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -49,6 +58,20 @@ fn load_config() -> Result<IntegrationConfig, Box<dyn std::error::Error>> {
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+
+    match cli.command {
+        None => run_default(cli).await,
+        Some(Command::Run) => run_live_integration(cli).await,
+        Some(Command::Script { file }) => run_script_file(cli, file).await,
+    }
+}
+
+/// The original behavior with no subcommand: Python controller drivers, the
+/// Go process manager, and the IPC handler bridge, all wired together. Kept
+/// as the default so existing deployments that invoke the binary with no
+/// arguments keep working unchanged.
+async fn run_default(cli: Cli) -> anyhow::Result<()> {
     println!("=======================================================");
     println!("           Neuro Desktop Control System");
     println!("=======================================================");
@@ -61,16 +84,19 @@ async fn main() -> anyhow::Result<()> {
         IntegrationConfig {
             connection: ConnectionConfig {
                 neuro_backend: "ws://localhost:8000".to_string(),
-            }
+            },
+            plugins: Vec::new(),
         }
     });
 
     // Configuration
-    let ws_url = env::var("NEURO_SDK_WS_URL")
-        .unwrap_or_else(|_| config.connection.neuro_backend.clone());
+    let ws_url = cli.ws_url.clone()
+        .or_else(|| env::var("NEURO_SDK_WS_URL").ok())
+        .unwrap_or_else(|| config.connection.neuro_backend.clone());
 
-    let ipc_path = env::var("NEURO_IPC_FILE")
-        .unwrap_or_else(|_| {
+    let ipc_path = cli.ipc_path.clone()
+        .or_else(|| env::var("NEURO_IPC_FILE").ok())
+        .unwrap_or_else(|| {
             env::current_exe()
                 .ok()
                 .and_then(|p| p.parent().map(|p| p.join("neuro_ipc.json")))
@@ -100,8 +126,8 @@ async fn main() -> anyhow::Result<()> {
 
     // Start IPC handler
     println!("[3/4] Starting IPC handler...");
-    let ipc = IPCHandler::new(&ipc_path);
-    let ipc_handler = ipc.start(controller);
+    let ipc = IPCHandler::with_plugins(&ipc_path, config.plugins.clone());
+    let (ipc_handler, drain_handle) = ipc.start(controller);
     println!("      ✓ IPC handler running on: {}", ipc_path);
     println!();
 
@@ -120,9 +146,17 @@ async fn main() -> anyhow::Result<()> {
     println!("Press Ctrl+C to stop");
     println!();
 
-    // Monitor Go process and restart if needed
+    // Monitor Go process and restart it on an unexpected exit. Restarts back
+    // off exponentially so a crash loop doesn't spin the machine, and the
+    // backoff resets after the process has stayed up for a while.
+    const INITIAL_BACKOFF: tokio::time::Duration = tokio::time::Duration::from_millis(500);
+    const MAX_BACKOFF: tokio::time::Duration = tokio::time::Duration::from_secs(30);
+    const HEALTHY_RESET_AFTER: tokio::time::Duration = tokio::time::Duration::from_secs(60);
+
     let mut check_interval = tokio::time::interval(tokio::time::Duration::from_secs(5));
-    
+    let mut restart_backoff = INITIAL_BACKOFF;
+    let mut last_restart = tokio::time::Instant::now();
+
     loop {
         tokio::select! {
             _ = check_interval.tick() => {
@@ -136,12 +170,35 @@ async fn main() -> anyhow::Result<()> {
 
                 // Check if Go process crashed
                 if !go_manager.is_running() {
-                    eprintln!("⚠ Neuro integration crashed! Attempting restart...");
-                    if let Err(e) = go_manager.restart(&ws_url, &ipc_path) {
-                        eprintln!("✗ Failed to restart Neuro integration: {}", e);
-                        break;
+                    eprintln!(
+                        "⚠ Neuro integration crashed! Restarting in {:?}...",
+                        restart_backoff
+                    );
+
+                    // Pause new IPC connections and give whatever command
+                    // was mid-flight a chance to finish (or get journaled)
+                    // before the process it depends on goes away.
+                    drain_handle.drain(std::time::Duration::from_secs(2));
+                    tokio::time::sleep(restart_backoff).await;
+
+                    match go_manager.restart(&ws_url, &ipc_path) {
+                        Ok(exit_status) => {
+                            if let Some(status) = exit_status {
+                                eprintln!("  previous process exit status: {}", status);
+                            }
+                            drain_handle.resume();
+                            println!("✓ Neuro integration restarted");
+                            last_restart = tokio::time::Instant::now();
+                            restart_backoff = (restart_backoff * 2).min(MAX_BACKOFF);
+                        }
+                        Err(e) => {
+                            eprintln!("✗ Failed to restart Neuro integration: {}", e);
+                            drain_handle.resume();
+                            break;
+                        }
                     }
-                    println!("✓ Neuro integration restarted");
+                } else if last_restart.elapsed() > HEALTHY_RESET_AFTER {
+                    restart_backoff = INITIAL_BACKOFF;
                 }
             }
 
@@ -156,4 +213,57 @@ async fn main() -> anyhow::Result<()> {
 
     println!("Neuro Desktop stopped");
     Ok(())
+}
+
+/// `run` subcommand: drive the live websocket integration directly, without
+/// the Go process/IPC bridge. `start_integration` builds its own Tokio
+/// runtime, so it's dispatched onto a blocking-pool thread to avoid nesting
+/// runtimes on top of the one `main` is already running in.
+async fn run_live_integration(cli: Cli) -> anyhow::Result<()> {
+    let controller = Controller::initialize_drivers()
+        .expect("Failed to initialize controller drivers");
+
+    let ws_url = cli.ws_url.clone();
+    let force_actions_interval = cli.force_actions_interval;
+    let dry_run = cli.dry_run;
+
+    tokio::task::spawn_blocking(move || {
+        integration::start_integration(controller, ws_url, force_actions_interval, dry_run);
+    })
+    .await?;
+
+    Ok(())
+}
+
+/// `script` subcommand: execute a single high-level command script against
+/// an `OsAgent` and print the result. Deliberately skips `Controller`
+/// initialization (which spawns its own Python drivers) so `--dry-run` can
+/// run this on a box with no desktop or Python runtime at all.
+async fn run_script_file(cli: Cli, file: String) -> anyhow::Result<()> {
+    let dry_run = cli.dry_run;
+
+    let result = tokio::task::spawn_blocking(move || {
+        let script_contents = std::fs::read_to_string(&file)
+            .map_err(|e| format!("failed to read {}: {}", file, e))?;
+
+        let agent = std::sync::Arc::new(std::sync::Mutex::new(if dry_run {
+            os_agent::OsAgent::dry_run()
+        } else {
+            os_agent::OsAgent::start()
+        }));
+
+        os_agent::run_script(agent, &script_contents)
+    })
+    .await?;
+
+    match result {
+        Ok(()) => {
+            println!("script executed");
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("script failed: {e}");
+            std::process::exit(1);
+        }
+    }
 }
\ No newline at end of file
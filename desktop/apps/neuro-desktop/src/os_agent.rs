@@ -1,19 +1,63 @@
-use std::process::{Command, Stdio};
-use std::io::{Write, BufRead, BufReader};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 
-use serde::Serialize;
+use mlua::{HookTriggers, Lua, Value as LuaValue};
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
 
-pub struct OsAgent {
-    stdin: std::process::ChildStdin,
-    stdout: BufReader<std::process::ChildStdout>,
+/// Either a real Python driver subprocess, or a logging stub that answers
+/// every call with success immediately. The stub exists so the script/action
+/// plumbing can be exercised (e.g. via `neuro-desktop script --dry-run`) on
+/// machines with no desktop to actually drive.
+pub enum OsAgent {
+    Real {
+        // Kept alive so the child isn't reaped while `stdin`/`stdout` are
+        // still in use; not otherwise read.
+        _child: Child,
+        stdin: ChildStdin,
+        stdout: BufReader<ChildStdout>,
+        /// Tags each outgoing command so its reply can be matched even if a
+        /// previous call's reply arrives late, instead of assuming strict
+        /// request/response ordering on the pipe.
+        next_id: AtomicU64,
+    },
+    Dry {
+        next_id: AtomicU64,
+    },
 }
 
 #[derive(Serialize)]
 struct CommandMsg<'a> {
+    id: u64,
     action: &'a str,
     x: Option<i32>,
     y: Option<i32>,
     text: Option<&'a str>,
+    /// Identifies which spawned process a `read_output`/`wait`/`send_stdin`
+    /// call targets; unused by the mouse/keyboard actions.
+    handle: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReplyMsg {
+    id: u64,
+    status: String,
+    #[serde(default)]
+    error: Option<String>,
+    /// Process handle returned by a `spawn` reply.
+    #[serde(default)]
+    handle: Option<u64>,
+    /// One line of stdout/stderr returned by a `read_output` reply.
+    #[serde(default)]
+    line: Option<String>,
+    /// Set once a `read_output` reply has no more output to give.
+    #[serde(default)]
+    eof: Option<bool>,
+    /// Exit code returned by a `wait` reply.
+    #[serde(default)]
+    exit_code: Option<i32>,
 }
 
 use rust_core::paths::{bundled_python, bundled_packages};
@@ -27,7 +71,7 @@ impl OsAgent {
 
         let python_lib = python_home.join("python").join("Lib");
         let python_site_packages = python_lib.join("site-packages");
-        
+
         println!("Python Lib: {}", python_lib.display());
 
         let mut child = Command::new(&python)
@@ -51,45 +95,257 @@ impl OsAgent {
         let stdin = child.stdin.take().unwrap();
         let stdout = BufReader::new(child.stdout.take().unwrap());
 
-        Self { stdin, stdout }
+        Self::Real { _child: child, stdin, stdout, next_id: AtomicU64::new(1) }
+    }
+
+    /// A stub that never spawns Python: every call logs what it would have
+    /// done and immediately reports success.
+    pub fn dry_run() -> Self {
+        println!("[dry-run] OS agent stub started (no Python driver spawned)");
+        Self::Dry { next_id: AtomicU64::new(1) }
     }
 
-    fn send(&mut self, msg: &impl Serialize) {
-        let json = serde_json::to_string(msg).unwrap();
-        writeln!(self.stdin, "{}", json).unwrap();
+    async fn call(
+        &mut self,
+        action: &str,
+        x: Option<i32>,
+        y: Option<i32>,
+        text: Option<&str>,
+        handle: Option<u64>,
+    ) -> Result<ReplyMsg, String> {
+        match self {
+            Self::Dry { next_id } => {
+                let id = next_id.fetch_add(1, Ordering::Relaxed);
+                println!("[dry-run] action={action} x={x:?} y={y:?} text={text:?} handle={handle:?} (id={id})");
+                Ok(ReplyMsg {
+                    id,
+                    status: "ok".to_string(),
+                    error: None,
+                    handle: Some(handle.unwrap_or(0)),
+                    line: None,
+                    eof: Some(true),
+                    exit_code: Some(0),
+                })
+            }
 
-        let mut response = String::new();
-        self.stdout.read_line(&mut response).unwrap();
+            Self::Real { stdin, stdout, next_id, .. } => {
+                let id = next_id.fetch_add(1, Ordering::Relaxed);
+                let json = serde_json::to_string(&CommandMsg { id, action, x, y, text, handle })
+                    .map_err(|e| e.to_string())?;
 
-        if !response.contains("\"ok\"") {
-            panic!("OS agent error: {}", response);
+                stdin.write_all(json.as_bytes()).await.map_err(|e| e.to_string())?;
+                stdin.write_all(b"\n").await.map_err(|e| e.to_string())?;
+                stdin.flush().await.map_err(|e| e.to_string())?;
+
+                loop {
+                    let mut line = String::new();
+                    let bytes_read = stdout.read_line(&mut line).await.map_err(|e| e.to_string())?;
+                    if bytes_read == 0 {
+                        return Err("OS agent closed its stdout".to_string());
+                    }
+
+                    let reply: ReplyMsg = serde_json::from_str(line.trim())
+                        .map_err(|e| format!("malformed OS agent reply: {e}"))?;
+
+                    // A reply for an older call that's still catching up;
+                    // keep reading until we see the one that matches this
+                    // request.
+                    if reply.id != id {
+                        continue;
+                    }
+
+                    return if reply.status == "ok" {
+                        Ok(reply)
+                    } else {
+                        Err(reply.error.unwrap_or(reply.status))
+                    };
+                }
+            }
         }
     }
 
-    pub fn move_mouse(&mut self, x: i32, y: i32) {
-        self.send(&CommandMsg {
-            action: "move_mouse",
-            x: Some(x),
-            y: Some(y),
-            text: None,
-        });
+    async fn send(&mut self, action: &str, x: Option<i32>, y: Option<i32>, text: Option<&str>) -> Result<(), String> {
+        self.call(action, x, y, text, None).await.map(|_| ())
+    }
+
+    pub async fn move_mouse(&mut self, x: i32, y: i32) -> Result<(), String> {
+        self.send("move_mouse", Some(x), Some(y), None).await
+    }
+
+    pub async fn click(&mut self, x: i32, y: i32) -> Result<(), String> {
+        self.send("click", Some(x), Some(y), None).await
+    }
+
+    pub async fn type_text(&mut self, text: &str) -> Result<(), String> {
+        self.send("type", None, None, Some(text)).await
+    }
+
+    /// Tell the Python driver to exit on its own, so it can tear down its
+    /// own state cleanly instead of being killed when the child is dropped.
+    pub async fn quit(&mut self) -> Result<(), String> {
+        self.send("quit", None, None, None).await
+    }
+
+    /// Start `command` in the driver and return a handle for subsequent
+    /// `read_line`/`send_stdin`/`wait` calls against it.
+    pub async fn spawn(&mut self, command: &str) -> Result<u64, String> {
+        let reply = self.call("spawn", None, None, Some(command), None).await?;
+        reply.handle.ok_or_else(|| "spawn reply missing a handle".to_string())
     }
 
-    pub fn click(&mut self, x: i32, y: i32) {
-        self.send(&CommandMsg {
-            action: "click",
-            x: Some(x),
-            y: Some(y),
-            text: None,
-        });
+    /// Read the next buffered line of stdout/stderr from the process behind
+    /// `handle`. Returns `None` once the process has no more output to give.
+    pub async fn read_line(&mut self, handle: u64) -> Result<Option<String>, String> {
+        let reply = self.call("read_output", None, None, None, Some(handle)).await?;
+        Ok(if reply.eof.unwrap_or(false) { None } else { reply.line })
     }
 
-    pub fn type_text(&mut self, text: &str) {
-        self.send(&CommandMsg {
-            action: "type",
-            x: None,
-            y: None,
-            text: Some(text),
-        });
+    /// Write `data` to the stdin of the process behind `handle`.
+    pub async fn send_stdin(&mut self, handle: u64, data: &str) -> Result<(), String> {
+        self.call("send_stdin", None, None, Some(data), Some(handle)).await.map(|_| ())
     }
+
+    /// Block until the process behind `handle` exits and return its exit code.
+    pub async fn wait(&mut self, handle: u64) -> Result<i32, String> {
+        let reply = self.call("wait", None, None, None, Some(handle)).await?;
+        reply.exit_code.ok_or_else(|| "wait reply missing an exit code".to_string())
+    }
+}
+
+/// Instruction budget for [`run_script`], counted in the hook's own stride
+/// (see `every_nth_instruction` below). Generous enough for any legitimate
+/// script, tight enough to kill an accidental infinite loop.
+const MAX_INSTRUCTIONS: u64 = 50_000_000;
+const HOOK_STRIDE: u32 = 10_000;
+
+/// Run `script_contents` as a sandboxed Lua program with `move_mouse(x, y)`,
+/// `click(x, y)`, `type_text(s)`, and the process primitives `spawn(cmd)`,
+/// `read_output(handle)`, `send_stdin(handle, s)`, `wait_process(handle)`
+/// wired to `agent`, so a high-level command script can branch on its own
+/// state instead of being a flat action list.
+///
+/// The sandbox strips `os`, `io`, `package` and `dofile` from the globals
+/// table before loading, and a debug hook aborts the script once it has run
+/// more than `MAX_INSTRUCTIONS` instructions, so a runaway script can't hang
+/// the process.
+///
+/// `OsAgent`'s calls are async (so they don't block `start_integration`'s
+/// websocket loop), but Lua closures aren't; each global call bridges onto a
+/// throwaway current-thread runtime for the duration of the script, mirroring
+/// how `ipc_handler::stream_events` drives its async `recv` from sync code.
+pub fn run_script(agent: Arc<Mutex<OsAgent>>, script_contents: &str) -> Result<(), String> {
+    let lua = Lua::new();
+
+    {
+        let globals = lua.globals();
+        for name in ["os", "io", "package", "dofile"] {
+            globals.set(name, LuaValue::Nil).map_err(|e| e.to_string())?;
+        }
+    }
+
+    let instructions_run = Arc::new(AtomicU64::new(0));
+    let hook_budget = Arc::clone(&instructions_run);
+    lua.set_hook(
+        HookTriggers::new().every_nth_instruction(HOOK_STRIDE),
+        move |_lua, _debug| {
+            let run = hook_budget.fetch_add(HOOK_STRIDE as u64, Ordering::Relaxed);
+            if run >= MAX_INSTRUCTIONS {
+                return Err(mlua::Error::RuntimeError(
+                    "script exceeded the instruction budget".to_string(),
+                ));
+            }
+            Ok(())
+        },
+    )
+    .map_err(|e| e.to_string())?;
+
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    lua.scope(|scope| {
+        let globals = lua.globals();
+
+        let move_agent = Arc::clone(&agent);
+        let move_rt = &runtime;
+        globals.set(
+            "move_mouse",
+            scope.create_function(move |_, (x, y): (i32, i32)| {
+                move_rt
+                    .block_on(async { move_agent.lock().unwrap().move_mouse(x, y).await })
+                    .map_err(mlua::Error::RuntimeError)
+            })?,
+        )?;
+
+        let click_agent = Arc::clone(&agent);
+        let click_rt = &runtime;
+        globals.set(
+            "click",
+            scope.create_function(move |_, (x, y): (i32, i32)| {
+                click_rt
+                    .block_on(async { click_agent.lock().unwrap().click(x, y).await })
+                    .map_err(mlua::Error::RuntimeError)
+            })?,
+        )?;
+
+        let type_agent = Arc::clone(&agent);
+        let type_rt = &runtime;
+        globals.set(
+            "type_text",
+            scope.create_function(move |_, text: String| {
+                type_rt
+                    .block_on(async { type_agent.lock().unwrap().type_text(&text).await })
+                    .map_err(mlua::Error::RuntimeError)
+            })?,
+        )?;
+
+        let spawn_agent = Arc::clone(&agent);
+        let spawn_rt = &runtime;
+        globals.set(
+            "spawn",
+            scope.create_function(move |_, command: String| {
+                spawn_rt
+                    .block_on(async { spawn_agent.lock().unwrap().spawn(&command).await })
+                    .map(|handle| handle as i64)
+                    .map_err(mlua::Error::RuntimeError)
+            })?,
+        )?;
+
+        let read_agent = Arc::clone(&agent);
+        let read_rt = &runtime;
+        globals.set(
+            "read_output",
+            scope.create_function(move |_, handle: i64| {
+                read_rt
+                    .block_on(async { read_agent.lock().unwrap().read_line(handle as u64).await })
+                    .map_err(mlua::Error::RuntimeError)
+            })?,
+        )?;
+
+        let stdin_agent = Arc::clone(&agent);
+        let stdin_rt = &runtime;
+        globals.set(
+            "send_stdin",
+            scope.create_function(move |_, (handle, data): (i64, String)| {
+                stdin_rt
+                    .block_on(async { stdin_agent.lock().unwrap().send_stdin(handle as u64, &data).await })
+                    .map_err(mlua::Error::RuntimeError)
+            })?,
+        )?;
+
+        let wait_agent = Arc::clone(&agent);
+        let wait_rt = &runtime;
+        globals.set(
+            "wait_process",
+            scope.create_function(move |_, handle: i64| {
+                wait_rt
+                    .block_on(async { wait_agent.lock().unwrap().wait(handle as u64).await })
+                    .map_err(mlua::Error::RuntimeError)
+            })?,
+        )?;
+
+        lua.load(script_contents).exec()
+    })
+    .map_err(|e| e.to_string())
 }
@@ -0,0 +1,294 @@
+// desktop/apps/neuro-desktop/src/plugin_manager.rs
+//
+// Spawns external plugin executables (piped stdin/stdout, one process per
+// plugin) so third parties can add new desktop-control commands without
+// touching this crate. Commands are dispatched as line-delimited JSON-RPC
+// 2.0 requests; plugins may call back into the shared `Controller` by
+// emitting their own JSON-RPC requests on stdout.
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, ChildStdout, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::controller::Controller;
+use crate::ipc_handler::IPCResponse;
+
+const CALL_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Deserialize)]
+struct HandshakeMsg {
+    commands: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct JsonRpcMsg {
+    #[serde(default)]
+    method: Option<String>,
+    #[serde(default)]
+    params: Option<Value>,
+    #[serde(default)]
+    result: Option<Value>,
+    #[serde(default)]
+    error: Option<Value>,
+    #[serde(default)]
+    id: Option<u64>,
+}
+
+struct PluginProcess {
+    path: String,
+    child: Child,
+    stdin: ChildStdin,
+    stdout: BufReader<ChildStdout>,
+}
+
+impl PluginProcess {
+    fn spawn(path: &str) -> Result<(Self, Vec<String>)> {
+        let mut child = Command::new(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .with_context(|| format!("failed to start plugin {}", path))?;
+
+        let stdin = child.stdin.take().context("plugin has no stdin")?;
+        let raw_stdout = child.stdout.take().context("plugin has no stdout")?;
+
+        // Handshake: the plugin's first line declares the command names it
+        // handles. Bounded the same as `call`'s timeout, on a helper thread
+        // (pipes have no read-timeout of their own), so a plugin that starts
+        // but never prints its handshake can't hang `PluginManager::load` --
+        // and with it, the whole process's boot -- forever.
+        let (tx, rx) = std::sync::mpsc::channel();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(raw_stdout);
+            let mut line = String::new();
+            let result = reader.read_line(&mut line).map(|n| (n, line));
+            let _ = tx.send((reader, result));
+        });
+
+        let (mut stdout, result) = rx
+            .recv_timeout(CALL_TIMEOUT)
+            .with_context(|| format!("plugin {} did not send its handshake within {:?}", path, CALL_TIMEOUT))?;
+        let (n, line) = result.with_context(|| format!("plugin {} closed before handshake", path))?;
+        if n == 0 {
+            bail!("plugin {} closed before handshake", path);
+        }
+        let handshake: HandshakeMsg = serde_json::from_str(line.trim())
+            .with_context(|| format!("plugin {} sent a malformed handshake: {}", path, line))?;
+
+        Ok((
+            Self {
+                path: path.to_string(),
+                child,
+                stdin,
+                stdout,
+            },
+            handshake.commands,
+        ))
+    }
+
+    fn call(&mut self, method: &str, params: Value, id: u64, controller: &Arc<Mutex<Controller>>) -> Result<JsonRpcMsg> {
+        let request = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+            "id": id,
+        });
+        writeln!(self.stdin, "{}", request)
+            .with_context(|| format!("failed to write to plugin {}", self.path))?;
+        self.stdin.flush().ok();
+
+        let deadline = Instant::now() + CALL_TIMEOUT;
+        loop {
+            if Instant::now() > deadline {
+                bail!("plugin {} timed out responding to '{}'", self.path, method);
+            }
+
+            let mut line = String::new();
+            let n = self
+                .stdout
+                .read_line(&mut line)
+                .with_context(|| format!("plugin {} pipe broke", self.path))?;
+            if n == 0 {
+                bail!("plugin {} closed its stdout", self.path);
+            }
+
+            let msg: JsonRpcMsg = serde_json::from_str(line.trim())
+                .with_context(|| format!("plugin {} sent invalid JSON-RPC: {}", self.path, line))?;
+
+            if let Some(callback_method) = msg.method.as_deref() {
+                // The plugin is calling back into the controller rather than
+                // replying to our request; dispatch it, write the response
+                // back on its stdin, then keep waiting for our own reply.
+                let response = match dispatch_callback(controller, callback_method, msg.params) {
+                    Ok(result) => serde_json::json!({ "jsonrpc": "2.0", "id": msg.id, "result": result }),
+                    Err(e) => serde_json::json!({ "jsonrpc": "2.0", "id": msg.id, "error": e }),
+                };
+                writeln!(self.stdin, "{}", response)
+                    .with_context(|| format!("failed to write callback response to plugin {}", self.path))?;
+                self.stdin.flush().ok();
+                continue;
+            }
+
+            if msg.id == Some(id) {
+                return Ok(msg);
+            }
+
+            bail!("plugin {} sent an out-of-order reply", self.path);
+        }
+    }
+}
+
+/// Route a plugin's callback JSON-RPC request (as opposed to a reply to our
+/// own outstanding call) into the shared `Controller`. Unknown methods are
+/// rejected rather than silently ignored, so a typo in a plugin surfaces
+/// immediately instead of looking like a no-op.
+fn dispatch_callback(controller: &Arc<Mutex<Controller>>, method: &str, params: Option<Value>) -> Result<Value, String> {
+    let params = params.unwrap_or(Value::Null);
+    let controller = controller.lock().unwrap();
+
+    match method {
+        "mouse_move" => {
+            let (x, y) = parse_xy(&params)?;
+            controller.mouse_move(x, y).map_err(|e| e.to_string())?;
+            Ok(Value::Null)
+        }
+        "mouse_click" => {
+            let (x, y) = parse_xy(&params)?;
+            controller.mouse_click(x, y, "left").map_err(|e| e.to_string())?;
+            Ok(Value::Null)
+        }
+        "type_text" => {
+            let text = params
+                .get("text")
+                .and_then(Value::as_str)
+                .ok_or("type_text callback missing 'text'")?;
+            controller.type_text(text).map_err(|e| e.to_string())?;
+            Ok(Value::Null)
+        }
+        "clear_action_queue" => {
+            controller.clear_action_queue().map_err(|e| e.to_string())?;
+            Ok(Value::Null)
+        }
+        "action_history" => controller
+            .action_history()
+            .map(Value::String)
+            .map_err(|e| e.to_string()),
+        other => Err(format!("unknown callback method '{}'", other)),
+    }
+}
+
+fn parse_xy(params: &Value) -> Result<(i32, i32), String> {
+    let x = params.get("x").and_then(Value::as_i64).ok_or("missing 'x'")? as i32;
+    let y = params.get("y").and_then(Value::as_i64).ok_or("missing 'y'")? as i32;
+    Ok((x, y))
+}
+
+/// Dispatches `IPCCommand::Custom` requests to the plugin that registered
+/// the matching command name.
+pub struct PluginManager {
+    routes: HashMap<String, String>,
+    processes: HashMap<String, Mutex<PluginProcess>>,
+    next_id: AtomicU64,
+}
+
+impl PluginManager {
+    /// Launch every plugin listed in `integration-config.yml`'s `plugins`
+    /// array and perform its startup handshake. A plugin that fails to start
+    /// is skipped, and a command name already claimed by an earlier plugin
+    /// is ignored for the later one, each with a logged warning rather than
+    /// aborting the whole manager. A plugin left with no commands of its own
+    /// is not kept running.
+    pub fn load(plugin_paths: &[String]) -> Self {
+        let mut routes = HashMap::new();
+        let mut processes = HashMap::new();
+
+        for path in plugin_paths {
+            let (process, commands) = match PluginProcess::spawn(path) {
+                Ok(v) => v,
+                Err(e) => {
+                    eprintln!("Plugin '{}' failed to start: {}", path, e);
+                    continue;
+                }
+            };
+
+            let mut accepted_any = false;
+            for command in commands {
+                if routes.contains_key(&command) {
+                    eprintln!(
+                        "Plugin '{}' tried to register command '{}' but it's already claimed; ignoring",
+                        path, command
+                    );
+                    continue;
+                }
+                routes.insert(command, path.clone());
+                accepted_any = true;
+            }
+
+            if accepted_any {
+                processes.insert(path.clone(), Mutex::new(process));
+            }
+        }
+
+        Self {
+            routes,
+            processes,
+            next_id: AtomicU64::new(1),
+        }
+    }
+
+    pub fn handles(&self, command: &str) -> bool {
+        self.routes.contains_key(command)
+    }
+
+    /// Forward a `Custom` IPC command to the plugin that owns it, restarting
+    /// the plugin process if it has died since the last call.
+    pub fn dispatch(&self, controller: &Arc<Mutex<Controller>>, command: &str, params: Value) -> IPCResponse {
+        let Some(path) = self.routes.get(command) else {
+            return IPCResponse::failure(format!("No plugin registered for command '{}'", command));
+        };
+        let Some(process) = self.processes.get(path) else {
+            return IPCResponse::failure(format!("Plugin '{}' is not running", path));
+        };
+
+        let mut process = process.lock().unwrap();
+
+        if let Ok(Some(_)) = process.child.try_wait() {
+            eprintln!("Plugin '{}' had crashed; restarting before dispatch", path);
+            match PluginProcess::spawn(path) {
+                Ok((fresh, _commands)) => *process = fresh,
+                Err(e) => return IPCResponse::failure(format!("Failed to restart plugin '{}': {}", path, e)),
+            }
+        }
+
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        match process.call(command, params, id, controller) {
+            Ok(msg) => {
+                if let Some(error) = msg.error {
+                    IPCResponse::failure(error.to_string())
+                } else {
+                    IPCResponse {
+                        success: true,
+                        data: msg.result,
+                        error: None,
+                    }
+                }
+            }
+            Err(e) => IPCResponse::failure(e.to_string()),
+        }
+    }
+}
+
+impl Drop for PluginProcess {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
@@ -6,4 +6,27 @@ pub fn get_python_packages_path() -> PathBuf {
     let root = exe.parent().unwrap();
 
     root.to_path_buf().join("python")
+}
+
+/// Root directory the bundled Python distribution lives under (the
+/// executable's own directory). Callers join this with `"python"` to reach
+/// the distribution itself, same as `get_python_packages_path`.
+pub fn bundled_packages() -> PathBuf {
+    let exe = env::current_exe().unwrap();
+    exe.parent().unwrap().to_path_buf()
+}
+
+/// Path to the bundled Python interpreter inside `bundled_packages()`.
+pub fn bundled_python() -> PathBuf {
+    let root = bundled_packages().join("python");
+
+    #[cfg(target_os = "windows")]
+    {
+        root.join("python.exe")
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        root.join("bin").join("python3")
+    }
 }
\ No newline at end of file